@@ -0,0 +1,174 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! The durable catalog: the transaction API used to read and mutate the
+//! current (latest) object model, and the historical version-to-version
+//! migrations under [`upgrade`].
+
+pub mod cluster_seal;
+pub mod upgrade;
+
+/// The current catalog object model. The write path below operates on the
+/// same `objects_v81` types the upgrade/compiler code already converts to
+/// and validates, rather than a separate shadow model, so the `sealed`
+/// field added by [`upgrade::v80_to_v81`] is the one actually enforced
+/// here.
+use crate::durable::upgrade::objects_v81 as objects;
+
+pub use cluster_seal::{ClusterSealStatus, SealedClusterOperation};
+
+/// Errors that can occur while reading or writing the durable catalog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CatalogError {
+    /// A write-path operation attempted to mutate a cluster while it is
+    /// sealed.
+    ClusterSealed { name: String, operation: String },
+    /// No cluster exists with the given id.
+    UnknownCluster(objects::ClusterId),
+}
+
+impl std::fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CatalogError::ClusterSealed { name, operation } => write!(
+                f,
+                "cluster {name} is sealed and cannot be modified by {operation}"
+            ),
+            CatalogError::UnknownCluster(id) => write!(f, "unknown cluster {id:?}"),
+        }
+    }
+}
+
+impl std::error::Error for CatalogError {}
+
+/// A live, in-progress catalog transaction. Mutating cluster operations go
+/// through the methods here (rather than writing `clusters` directly) so
+/// that write-path guards, like the sealed-cluster check, run uniformly.
+///
+/// Replica names are tracked separately from `clusters`, mirroring how
+/// cluster replicas are their own durable object distinct from the
+/// `Cluster` state update the migration converts.
+pub struct Transaction<'a> {
+    clusters: &'a mut std::collections::BTreeMap<objects::ClusterId, objects::Cluster>,
+    replicas: &'a mut std::collections::BTreeMap<objects::ClusterId, Vec<String>>,
+}
+
+impl<'a> Transaction<'a> {
+    pub fn new(
+        clusters: &'a mut std::collections::BTreeMap<objects::ClusterId, objects::Cluster>,
+        replicas: &'a mut std::collections::BTreeMap<objects::ClusterId, Vec<String>>,
+    ) -> Self {
+        Transaction { clusters, replicas }
+    }
+
+    pub fn get_cluster(&self, id: objects::ClusterId) -> Result<objects::Cluster, CatalogError> {
+        self.clusters
+            .get(&id)
+            .cloned()
+            .ok_or(CatalogError::UnknownCluster(id))
+    }
+
+    pub fn update_cluster(
+        &mut self,
+        id: objects::ClusterId,
+        cluster: objects::Cluster,
+    ) -> Result<(), CatalogError> {
+        self.clusters.insert(id, cluster);
+        Ok(())
+    }
+
+    pub fn replica_names(&self, id: objects::ClusterId) -> Vec<String> {
+        self.replicas.get(&id).cloned().unwrap_or_default()
+    }
+
+    /// `ALTER CLUSTER ... SET (...)`. Rejected while the cluster is sealed.
+    pub fn alter_cluster(
+        &mut self,
+        id: objects::ClusterId,
+        workload_class: Option<String>,
+    ) -> Result<(), CatalogError> {
+        self.ensure_cluster_unsealed(id, SealedClusterOperation::AlterCluster)?;
+        let mut cluster = self.get_cluster(id)?;
+        cluster.value.config.workload_class = workload_class;
+        self.update_cluster(id, cluster)
+    }
+
+    /// `CREATE CLUSTER REPLICA`. Rejected while the cluster is sealed.
+    pub fn create_cluster_replica(
+        &mut self,
+        id: objects::ClusterId,
+        replica_name: String,
+    ) -> Result<(), CatalogError> {
+        self.ensure_cluster_unsealed(id, SealedClusterOperation::CreateReplica)?;
+        self.get_cluster(id)?;
+        self.replicas.entry(id).or_default().push(replica_name);
+        Ok(())
+    }
+
+    /// `DROP CLUSTER REPLICA`. Rejected while the cluster is sealed.
+    pub fn drop_cluster_replica(
+        &mut self,
+        id: objects::ClusterId,
+        replica_name: &str,
+    ) -> Result<(), CatalogError> {
+        self.ensure_cluster_unsealed(id, SealedClusterOperation::DropReplica)?;
+        self.get_cluster(id)?;
+        if let Some(names) = self.replicas.get_mut(&id) {
+            names.retain(|name| name != replica_name);
+        }
+        Ok(())
+    }
+
+    /// `ALTER CLUSTER ... SCHEDULE`. Rejected while the cluster is sealed.
+    pub fn alter_cluster_schedule(
+        &mut self,
+        id: objects::ClusterId,
+        schedule: objects::ClusterSchedule,
+    ) -> Result<(), CatalogError> {
+        self.ensure_cluster_unsealed(id, SealedClusterOperation::AlterSchedule)?;
+        let mut cluster = self.get_cluster(id)?;
+        if let objects::ClusterVariant::Managed(managed) = &mut cluster.value.config.variant {
+            managed.schedule = schedule;
+        }
+        self.update_cluster(id, cluster)
+    }
+
+    /// `ALTER CLUSTER ... (SIZE = ...)`. Rejected while the cluster is
+    /// sealed.
+    pub fn resize_cluster(
+        &mut self,
+        id: objects::ClusterId,
+        size: String,
+    ) -> Result<(), CatalogError> {
+        self.ensure_cluster_unsealed(id, SealedClusterOperation::Resize)?;
+        let mut cluster = self.get_cluster(id)?;
+        if let objects::ClusterVariant::Managed(managed) = &mut cluster.value.config.variant {
+            managed.size = size;
+        }
+        self.update_cluster(id, cluster)
+    }
+
+    /// Structured cluster-status output. Reads are never blocked by
+    /// sealing, so this does not call `ensure_cluster_unsealed`.
+    pub fn cluster_status(&self, id: objects::ClusterId) -> Result<ClusterStatus, CatalogError> {
+        let cluster = self.get_cluster(id)?;
+        Ok(ClusterStatus {
+            name: cluster.value.name,
+            sealed: cluster.value.config.sealed,
+        })
+    }
+}
+
+/// Structured cluster-status output, including whether the cluster is
+/// currently sealed and therefore rejecting mutating operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterStatus {
+    pub name: String,
+    pub sealed: bool,
+}
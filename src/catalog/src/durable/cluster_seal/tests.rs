@@ -0,0 +1,145 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::BTreeMap;
+
+use super::*;
+use crate::durable::upgrade::objects_v81::{
+    ClusterConfig, ClusterKey, ClusterSchedule, ClusterScheduleRefreshOptions, ClusterValue,
+    ClusterVariant, Duration, ManagedCluster, ReplicaLogging, RoleId,
+};
+
+fn managed_cluster(id: ClusterId, sealed: bool) -> Cluster {
+    Cluster {
+        key: ClusterKey { id },
+        value: ClusterValue {
+            name: "test_cluster".to_string(),
+            owner_id: RoleId::User(1),
+            privileges: Vec::new(),
+            config: ClusterConfig {
+                workload_class: None,
+                variant: ClusterVariant::Managed(ManagedCluster {
+                    size: "25cc".to_string(),
+                    replication_factor: 1,
+                    availability_zones: Vec::new(),
+                    logging: ReplicaLogging {
+                        log_logging: false,
+                        interval: None,
+                    },
+                    optimizer_feature_overrides: Vec::new(),
+                    schedule: ClusterSchedule::Manual,
+                }),
+                sealed,
+            },
+        },
+    }
+}
+
+fn catalog_with_cluster(
+    id: ClusterId,
+    sealed: bool,
+) -> (
+    BTreeMap<ClusterId, Cluster>,
+    BTreeMap<ClusterId, Vec<String>>,
+) {
+    (
+        BTreeMap::from([(id, managed_cluster(id, sealed))]),
+        BTreeMap::from([(id, vec!["r1".to_string()])]),
+    )
+}
+
+fn refresh_schedule() -> ClusterSchedule {
+    ClusterSchedule::Refresh(ClusterScheduleRefreshOptions {
+        rehydration_time_estimate: Duration { secs: 60, nanos: 0 },
+    })
+}
+
+#[test]
+fn sealed_cluster_rejects_every_mutating_operation() {
+    let id = ClusterId::User(1);
+    let (mut clusters, mut replicas) = catalog_with_cluster(id, true);
+    let mut txn = Transaction::new(&mut clusters, &mut replicas);
+
+    assert!(matches!(
+        txn.alter_cluster(id, Some("batch".to_string())),
+        Err(CatalogError::ClusterSealed { .. })
+    ));
+    assert!(matches!(
+        txn.create_cluster_replica(id, "r2".to_string()),
+        Err(CatalogError::ClusterSealed { .. })
+    ));
+    assert!(matches!(
+        txn.drop_cluster_replica(id, "r1"),
+        Err(CatalogError::ClusterSealed { .. })
+    ));
+    assert!(matches!(
+        txn.alter_cluster_schedule(id, refresh_schedule()),
+        Err(CatalogError::ClusterSealed { .. })
+    ));
+    assert!(matches!(
+        txn.resize_cluster(id, "50cc".to_string()),
+        Err(CatalogError::ClusterSealed { .. })
+    ));
+
+    // None of the rejected operations actually mutated the cluster.
+    let cluster = txn.get_cluster(id).unwrap();
+    assert_eq!(cluster.value.config.workload_class, None);
+    assert_eq!(txn.replica_names(id), vec!["r1".to_string()]);
+}
+
+#[test]
+fn unsealed_cluster_accepts_every_mutating_operation() {
+    let id = ClusterId::User(1);
+    let (mut clusters, mut replicas) = catalog_with_cluster(id, false);
+    let mut txn = Transaction::new(&mut clusters, &mut replicas);
+
+    txn.alter_cluster(id, Some("batch".to_string())).unwrap();
+    txn.create_cluster_replica(id, "r2".to_string()).unwrap();
+    txn.drop_cluster_replica(id, "r1").unwrap();
+    txn.alter_cluster_schedule(id, refresh_schedule()).unwrap();
+    txn.resize_cluster(id, "50cc".to_string()).unwrap();
+
+    let cluster = txn.get_cluster(id).unwrap();
+    assert_eq!(
+        cluster.value.config.workload_class,
+        Some("batch".to_string())
+    );
+    assert_eq!(txn.replica_names(id), vec!["r2".to_string()]);
+    assert_eq!(cluster.value.name, "test_cluster");
+}
+
+#[test]
+fn reads_continue_against_a_sealed_cluster() {
+    let id = ClusterId::User(1);
+    let (mut clusters, mut replicas) = catalog_with_cluster(id, true);
+    let txn = Transaction::new(&mut clusters, &mut replicas);
+
+    let status = txn.cluster_status(id).unwrap();
+    assert_eq!(status.name, "test_cluster");
+    assert!(status.sealed);
+    assert!(txn.cluster_seal_status(id).unwrap().is_sealed());
+}
+
+#[test]
+fn seal_and_unseal_round_trip() {
+    let id = ClusterId::User(1);
+    let (mut clusters, mut replicas) = catalog_with_cluster(id, false);
+    let mut txn = Transaction::new(&mut clusters, &mut replicas);
+
+    txn.seal_cluster(id).unwrap();
+    assert!(txn.cluster_seal_status(id).unwrap().is_sealed());
+    assert!(matches!(
+        txn.alter_cluster(id, None),
+        Err(CatalogError::ClusterSealed { .. })
+    ));
+
+    txn.unseal_cluster(id).unwrap();
+    assert!(!txn.cluster_seal_status(id).unwrap().is_sealed());
+    txn.alter_cluster(id, None).unwrap();
+}
@@ -0,0 +1,181 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A migration compiler that fuses the registered per-version transforms
+//! into a single pass over the snapshot.
+//!
+//! Applying version steps one at a time re-walks and re-clones the entire
+//! snapshot at every step, so a jump of N versions costs O(objects * N).
+//! [`MigrationPlan`] instead records, per object kind, which steps actually
+//! touch it, and [`migrate_composed`] runs each object through all of them
+//! in turn, so no intermediate snapshot is built and steps that do not touch
+//! an object's kind cost nothing for it. Objects travel between steps as
+//! `serde_json::Value`s and a step may decode and re-encode the value it is
+//! given, so the work per object grows with the number of steps that touch
+//! its kind.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::durable::upgrade::objects_v81 as latest;
+
+/// Identifies the kind of catalog object a version step's transform
+/// touches, so the compiler can skip steps that are pure JSON-compatible
+/// passthroughs for a given kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ObjectKind {
+    Cluster,
+    Role,
+    Other,
+}
+
+/// One registered version step in a chained migration. The transform is
+/// expressed over the wire representation (`serde_json::Value`) rather than
+/// a version-specific Rust type, so steps belonging to different versions
+/// can be composed into a single pipeline.
+///
+/// A boxed closure rather than a plain `fn` pointer, so a step can capture
+/// shared, mutable state — in particular a memoization cache — across every
+/// object it's applied to, and so it can surface a malformed object as an
+/// error instead of panicking and taking down the whole fused migration.
+pub struct VersionStep {
+    pub version: u64,
+    pub touches: BTreeSet<ObjectKind>,
+    pub transform: Box<
+        dyn Fn(ObjectKind, serde_json::Value) -> Result<serde_json::Value, String> + Send + Sync,
+    >,
+}
+
+/// A compiled chain of [`VersionStep`]s: for each object kind, the ordered
+/// list of step indices that actually need to run against it. Steps that
+/// are pure passthroughs for a kind are skipped entirely rather than
+/// applied as identity transforms.
+pub struct MigrationPlan {
+    steps_by_kind: BTreeMap<ObjectKind, Vec<usize>>,
+    steps: Vec<VersionStep>,
+}
+
+impl MigrationPlan {
+    /// Compiles `steps` (oldest version first) into a plan.
+    pub fn compile(steps: Vec<VersionStep>) -> Self {
+        let mut steps_by_kind: BTreeMap<ObjectKind, Vec<usize>> = BTreeMap::new();
+        for (index, step) in steps.iter().enumerate() {
+            for kind in &step.touches {
+                steps_by_kind.entry(*kind).or_default().push(index);
+            }
+        }
+        MigrationPlan {
+            steps_by_kind,
+            steps,
+        }
+    }
+
+    /// Which registered version numbers actually touch `kind`.
+    pub fn versions_touching(&self, kind: ObjectKind) -> Vec<u64> {
+        self.steps_by_kind
+            .get(&kind)
+            .into_iter()
+            .flatten()
+            .map(|&index| self.steps[index].version)
+            .collect()
+    }
+
+    fn apply(
+        &self,
+        kind: ObjectKind,
+        mut value: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        if let Some(indices) = self.steps_by_kind.get(&kind) {
+            for &index in indices {
+                value = (self.steps[index].transform)(kind, value)?;
+            }
+        }
+        Ok(value)
+    }
+}
+
+/// Runs `snapshot` through `plan` in a single fused pass — each object goes
+/// through every applicable version step in turn, then is deserialized into
+/// the latest type once — and performs one post-migration validation over
+/// the fully-migrated result before returning it.
+pub fn migrate_composed(
+    snapshot: Vec<(ObjectKind, serde_json::Value)>,
+    plan: &MigrationPlan,
+) -> Result<Vec<latest::StateUpdateKind>, String> {
+    let objects: Vec<latest::StateUpdateKind> = snapshot
+        .into_iter()
+        .map(|(kind, value)| {
+            let migrated = plan.apply(kind, value)?;
+            serde_json::from_value(migrated)
+                .map_err(|err| format!("fused migration produced an invalid state update: {err}"))
+        })
+        .collect::<Result<_, _>>()?;
+
+    validate_referential_integrity(&objects)?;
+    Ok(objects)
+}
+
+/// Whether `id` is one of the built-in role kinds (system, public,
+/// predefined) that are always considered to exist without an explicit
+/// `Role` state update, as opposed to a `User` role, which must have one.
+fn role_id_is_builtin(id: &latest::RoleId) -> bool {
+    match id {
+        latest::RoleId::System(_) | latest::RoleId::Public | latest::RoleId::Predefined(_) => true,
+        latest::RoleId::User(_) => false,
+    }
+}
+
+/// Validates the fully-migrated snapshot: every cluster's
+/// `owner_id`/`grantee`/`grantor` must reference a role that exists (built-in
+/// roles always do; `User` roles need a matching `Role` state update), and
+/// cluster ids must be unique.
+fn validate_referential_integrity(objects: &[latest::StateUpdateKind]) -> Result<(), String> {
+    let mut role_ids = HashSet::new();
+    let mut cluster_ids = HashSet::new();
+    for object in objects {
+        match object {
+            latest::StateUpdateKind::Role(role) => {
+                role_ids.insert(role.key.id.clone());
+            }
+            latest::StateUpdateKind::Cluster(cluster) => {
+                if !cluster_ids.insert(cluster.key.id.clone()) {
+                    return Err(format!("duplicate cluster id {:?}", cluster.key.id));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let role_exists = |id: &latest::RoleId| role_id_is_builtin(id) || role_ids.contains(id);
+
+    for object in objects {
+        let latest::StateUpdateKind::Cluster(cluster) = object else {
+            continue;
+        };
+        if !role_exists(&cluster.value.owner_id) {
+            return Err(format!(
+                "cluster {:?} has owner_id {:?} with no matching role",
+                cluster.key.id, cluster.value.owner_id
+            ));
+        }
+        for item in &cluster.value.privileges {
+            for id in [&item.grantee, &item.grantor] {
+                if !role_exists(id) {
+                    return Err(format!(
+                        "cluster {:?} has a privilege referencing unknown role {:?}",
+                        cluster.key.id, id
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,127 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use super::*;
+use crate::durable::upgrade::v80_to_v81;
+
+fn cluster_owned_by(owner_id: latest::RoleId) -> latest::StateUpdateKind {
+    latest::StateUpdateKind::Cluster(latest::Cluster {
+        key: latest::ClusterKey {
+            id: latest::ClusterId::User(1),
+        },
+        value: latest::ClusterValue {
+            name: "c".to_string(),
+            owner_id,
+            privileges: Vec::new(),
+            config: latest::ClusterConfig {
+                workload_class: None,
+                variant: latest::ClusterVariant::Unmanaged,
+                sealed: false,
+            },
+        },
+    })
+}
+
+#[test]
+fn builtin_role_ids_never_require_a_role_entry() {
+    for owner_id in [
+        latest::RoleId::System(1),
+        latest::RoleId::Public,
+        latest::RoleId::Predefined(1),
+    ] {
+        let objects = vec![cluster_owned_by(owner_id)];
+        assert!(validate_referential_integrity(&objects).is_ok());
+    }
+}
+
+#[test]
+fn user_role_id_requires_a_matching_role_entry() {
+    let objects = vec![cluster_owned_by(latest::RoleId::User(1))];
+    assert!(validate_referential_integrity(&objects).is_err());
+
+    let objects_with_role = vec![
+        latest::StateUpdateKind::Role(latest::Role {
+            key: latest::RoleKey {
+                id: latest::RoleId::User(1),
+            },
+            value: latest::RoleValue {
+                name: "materialize".to_string(),
+            },
+        }),
+        cluster_owned_by(latest::RoleId::User(1)),
+    ];
+    assert!(validate_referential_integrity(&objects_with_role).is_ok());
+}
+
+#[test]
+fn duplicate_cluster_ids_are_rejected() {
+    let objects = vec![
+        cluster_owned_by(latest::RoleId::System(1)),
+        cluster_owned_by(latest::RoleId::System(1)),
+    ];
+    assert!(validate_referential_integrity(&objects).is_err());
+}
+
+#[test]
+fn migrate_composed_propagates_transform_errors_instead_of_panicking() {
+    let plan = MigrationPlan::compile(vec![VersionStep {
+        version: 1,
+        touches: [ObjectKind::Cluster].into_iter().collect(),
+        transform: Box::new(|_kind, _value| Err("object is malformed".to_string())),
+    }]);
+
+    let snapshot = vec![(
+        ObjectKind::Cluster,
+        serde_json::json!({ "not": "a cluster" }),
+    )];
+    let err = migrate_composed(snapshot, &plan).unwrap_err();
+    assert!(err.contains("malformed"));
+}
+
+/// The v80 cluster fixture added alongside the property-based test harness
+/// has a `System`-owned cluster and a `User`-owned cluster with no `Role`
+/// entries other than the `User` one, so this exercises both the built-in
+/// role special-casing and the `User`-role lookup in one pass.
+#[test]
+fn migrate_composed_accepts_the_v80_cluster_fixture() {
+    let raw = include_str!("../testdata/v80_cluster_snapshot.json");
+    let entries: Vec<serde_json::Value> = serde_json::from_str(raw).expect("fixture is valid JSON");
+
+    let snapshot: Vec<(ObjectKind, serde_json::Value)> = entries
+        .into_iter()
+        .map(|entry| {
+            let kind = if entry.get("Cluster").is_some() {
+                ObjectKind::Cluster
+            } else if entry.get("Role").is_some() {
+                ObjectKind::Role
+            } else {
+                ObjectKind::Other
+            };
+            (kind, entry)
+        })
+        .collect();
+
+    let plan = MigrationPlan::compile(vec![v80_to_v81::as_version_step()]);
+    migrate_composed(snapshot, &plan).expect("fixture migrates and validates cleanly");
+}
+
+#[test]
+fn migrate_composed_skips_steps_that_do_not_touch_the_kind() {
+    let plan = MigrationPlan::compile(vec![VersionStep {
+        version: 1,
+        touches: [ObjectKind::Role].into_iter().collect(),
+        transform: Box::new(|_kind, _value| Err("should never run for Cluster".to_string())),
+    }]);
+
+    let cluster = cluster_owned_by(latest::RoleId::System(1));
+    let value = serde_json::to_value(&cluster).expect("Cluster serializes");
+    let snapshot = vec![(ObjectKind::Cluster, value)];
+    let migrated = migrate_composed(snapshot, &plan).expect("Role-only step skips Cluster");
+    assert_eq!(migrated, vec![cluster]);
+}
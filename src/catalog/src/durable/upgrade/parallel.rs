@@ -0,0 +1,96 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A parallel, order-preserving executor for per-object migration
+//! transforms, used by version steps whose snapshots are large enough that
+//! a single-threaded `into_iter().filter_map()` becomes the bottleneck.
+
+use std::hash::Hash;
+
+use dashmap::DashMap;
+use rayon::prelude::*;
+
+use crate::durable::upgrade::MigrationAction;
+
+/// Runs `transform` over `snapshot` across a rayon thread pool and
+/// reassembles the resulting [`MigrationAction`]s in the original input
+/// order.
+///
+/// `transform` returns `None` for objects that are JSON-compatible between
+/// the two versions and therefore need no `MigrationAction`, mirroring the
+/// `filter_map` convention used by the single-threaded `upgrade` functions.
+/// Output order is made deterministic by carrying each element's original
+/// index alongside its result and sorting before emitting, so switching a
+/// version step between the serial and parallel executor never changes the
+/// byte-for-byte output.
+pub fn migrate_parallel<Old, New, F>(
+    snapshot: Vec<Old>,
+    transform: F,
+) -> Vec<MigrationAction<Old, New>>
+where
+    Old: Clone + Send,
+    New: Send,
+    F: Fn(Old) -> Option<New> + Sync,
+{
+    let mut indexed: Vec<(usize, Option<MigrationAction<Old, New>>)> = snapshot
+        .into_par_iter()
+        .enumerate()
+        .map(|(index, old)| {
+            let action = transform(old.clone()).map(|new| MigrationAction::Update(old, new));
+            (index, action)
+        })
+        .collect();
+    indexed.sort_unstable_by_key(|(index, _)| *index);
+    indexed
+        .into_iter()
+        .filter_map(|(_, action)| action)
+        .collect()
+}
+
+/// A concurrent cache (a `DashMap`, whose shards are guarded by locks) of
+/// structurally-identical sub-value conversions,
+/// shared across the worker threads driven by [`migrate_parallel`] so that
+/// a shape repeated across many objects (e.g. the same `ReplicaLogging` or
+/// `OptimizerFeatureOverride` on many clusters) is only converted once.
+pub struct ConversionCache<K, V> {
+    cells: DashMap<K, V>,
+}
+
+impl<K, V> ConversionCache<K, V>
+where
+    K: Eq + Hash,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        ConversionCache {
+            cells: DashMap::new(),
+        }
+    }
+
+    /// Returns the cached conversion for `key`, computing and storing it
+    /// via `convert` on first use.
+    pub fn get_or_convert(&self, key: K, convert: impl FnOnce() -> V) -> V {
+        if let Some(cached) = self.cells.get(&key) {
+            return cached.clone();
+        }
+        // Converting under the entry's lock means concurrent first uses of
+        // the same key still run `convert` only once.
+        self.cells.entry(key).or_insert_with(convert).value().clone()
+    }
+}
+
+impl<K, V> Default for ConversionCache<K, V>
+where
+    K: Eq + Hash,
+    V: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
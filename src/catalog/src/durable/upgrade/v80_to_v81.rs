@@ -10,85 +10,300 @@
 use crate::durable::upgrade::MigrationAction;
 use crate::durable::upgrade::objects_v80 as v80;
 use crate::durable::upgrade::objects_v81 as v81;
+use crate::durable::upgrade::parallel::{ConversionCache, migrate_parallel};
+
+#[cfg(test)]
+mod tests;
+
+impl<Old, New> MigrationAction<Old, New> {
+    /// Inverts an `Update` action, swapping its old and new values. Lets a
+    /// `downgrade` build its action in the same `(old, new)` order as the
+    /// paired `upgrade` (old = the v_n value, new = the v_n+1 value) and
+    /// then flip it into the `MigrationAction<New, Old>` shape `downgrade`
+    /// actually returns, instead of swapping the two values by hand at each
+    /// call site.
+    pub fn invert(self) -> MigrationAction<New, Old> {
+        match self {
+            MigrationAction::Update(old, new) => MigrationAction::Update(new, old),
+        }
+    }
+}
 
 /// Migration from v80 to v81: adds `sealed` field to ClusterConfig.
 ///
 /// The `sealed` field is a new boolean field that defaults to `false`.
 /// All existing clusters will have `sealed = false` after this migration.
+///
+/// Runs via [`migrate_parallel`]: for large catalogs this spreads the
+/// per-cluster transform across a rayon thread pool while still producing
+/// byte-for-byte deterministic output, since `migrate_parallel` reassembles
+/// actions in the original snapshot order.
 pub fn upgrade(
     snapshot: Vec<v80::StateUpdateKind>,
 ) -> Vec<MigrationAction<v80::StateUpdateKind, v81::StateUpdateKind>> {
+    let caches = UpgradeCaches::new();
+    migrate_parallel(snapshot, |old| upgrade_state_update(old, &caches))
+}
+
+/// The sub-value conversions that are worth memoizing across objects:
+/// `ReplicaLogging` and the `OptimizerFeatureOverride` list. These are kept
+/// as separate caches, keyed on the sub-value itself rather than on the
+/// whole `ClusterVariant`, so two clusters that share the same logging
+/// config or override list still cache-hit even if they differ in size,
+/// replication factor, or availability zones.
+struct UpgradeCaches {
+    logging: ConversionCache<v80::ReplicaLogging, v81::ReplicaLogging>,
+    optimizer_feature_overrides:
+        ConversionCache<Vec<v80::OptimizerFeatureOverride>, Vec<v81::OptimizerFeatureOverride>>,
+}
+
+impl UpgradeCaches {
+    fn new() -> Self {
+        UpgradeCaches {
+            logging: ConversionCache::new(),
+            optimizer_feature_overrides: ConversionCache::new(),
+        }
+    }
+}
+
+/// Registers this version step with the migration compiler in
+/// [`crate::durable::upgrade::compiler`]. Only `Cluster` is touched; every
+/// other kind is a pure passthrough for v80 -> v81, so a composed multi-
+/// version migration skips this step entirely for them.
+///
+/// The caches are created once and captured by the returned closure, so
+/// they're shared across every object the compiler runs through this step
+/// rather than being thrown away after a single use.
+pub fn as_version_step() -> crate::durable::upgrade::compiler::VersionStep {
+    use crate::durable::upgrade::compiler::{ObjectKind, VersionStep};
+
+    let caches = UpgradeCaches::new();
+
+    VersionStep {
+        version: 80,
+        touches: [ObjectKind::Cluster].into_iter().collect(),
+        transform: Box::new(move |kind, value| match kind {
+            ObjectKind::Cluster => {
+                let old: v80::StateUpdateKind = serde_json::from_value(value)
+                    .map_err(|err| format!("value is not a v80 Cluster: {err}"))?;
+                let new = upgrade_state_update(old, &caches)
+                    .ok_or_else(|| "value labelled Cluster is not a v80 Cluster".to_string())?;
+                serde_json::to_value(new)
+                    .map_err(|err| format!("migrated Cluster failed to serialize: {err}"))
+            }
+            _ => Ok(value),
+        }),
+    }
+}
+
+fn upgrade_state_update(
+    old: v80::StateUpdateKind,
+    caches: &UpgradeCaches,
+) -> Option<v81::StateUpdateKind> {
+    match old {
+        v80::StateUpdateKind::Cluster(cluster) => {
+            let variant = cluster.value.config.variant;
+            let upgraded_variant = upgrade_cluster_variant(variant, caches);
+            Some(v81::StateUpdateKind::Cluster(v81::Cluster {
+                key: v81::ClusterKey {
+                    id: upgrade_cluster_id(cluster.key.id),
+                },
+                value: v81::ClusterValue {
+                    name: cluster.value.name,
+                    owner_id: upgrade_role_id(cluster.value.owner_id),
+                    privileges: cluster
+                        .value
+                        .privileges
+                        .into_iter()
+                        .map(upgrade_mz_acl_item)
+                        .collect(),
+                    config: v81::ClusterConfig {
+                        workload_class: cluster.value.config.workload_class,
+                        variant: upgraded_variant,
+                        // New field: default to false (unsealed)
+                        sealed: false,
+                    },
+                },
+            }))
+        }
+        // All other types are JSON-compatible between v80 and v81
+        _ => None,
+    }
+}
+
+fn upgrade_cluster_id(id: v80::ClusterId) -> v81::ClusterId {
+    match id {
+        v80::ClusterId::System(id) => v81::ClusterId::System(id),
+        v80::ClusterId::User(id) => v81::ClusterId::User(id),
+    }
+}
+
+fn upgrade_role_id(id: v80::RoleId) -> v81::RoleId {
+    match id {
+        v80::RoleId::System(id) => v81::RoleId::System(id),
+        v80::RoleId::User(id) => v81::RoleId::User(id),
+        v80::RoleId::Public => v81::RoleId::Public,
+        v80::RoleId::Predefined(id) => v81::RoleId::Predefined(id),
+    }
+}
+
+fn upgrade_mz_acl_item(item: v80::MzAclItem) -> v81::MzAclItem {
+    v81::MzAclItem {
+        grantee: upgrade_role_id(item.grantee),
+        grantor: upgrade_role_id(item.grantor),
+        acl_mode: v81::AclMode {
+            bitflags: item.acl_mode.bitflags,
+        },
+    }
+}
+
+/// Per-field policy controlling how [`downgrade`] handles a field that was
+/// introduced in v81 and has no representation in v80.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DowngradePolicy {
+    /// Drop the field's value without inspecting it. Appropriate for fields
+    /// whose value carries no durable meaning once the older binary is in
+    /// charge.
+    DropSilently,
+    /// Drop the field's value, but only after confirming it already equals
+    /// its type's default; otherwise the whole object is omitted from the
+    /// downgraded snapshot.
+    RequireDefault,
+    /// Refuse the downgrade outright if the field's value differs from its
+    /// type's default, since dropping it would silently change behavior.
+    AbortIfNonDefault,
+}
+
+/// The result of checking a field's value against its [`DowngradePolicy`].
+#[derive(Debug, PartialEq, Eq)]
+enum PolicyOutcome {
+    /// The field may be dropped; continue downgrading this object.
+    Proceed,
+    /// The field's value isn't representable in the older version, but the
+    /// blast radius is limited to this one object: omit it from the
+    /// downgraded snapshot rather than failing the whole migration.
+    SkipObject,
+    /// The field's value isn't representable in the older version, and
+    /// silently dropping or skipping it would hide a real state change:
+    /// abort the whole downgrade with this message.
+    Abort(String),
+}
+
+/// Applies `policy` to a single field's `value`.
+fn apply_downgrade_policy<T: PartialEq + Default + std::fmt::Debug>(
+    policy: DowngradePolicy,
+    field_name: &str,
+    value: &T,
+) -> PolicyOutcome {
+    match policy {
+        DowngradePolicy::DropSilently => PolicyOutcome::Proceed,
+        DowngradePolicy::RequireDefault => {
+            if *value == T::default() {
+                PolicyOutcome::Proceed
+            } else {
+                PolicyOutcome::SkipObject
+            }
+        }
+        DowngradePolicy::AbortIfNonDefault => {
+            if *value == T::default() {
+                PolicyOutcome::Proceed
+            } else {
+                PolicyOutcome::Abort(format!(
+                    "cannot downgrade catalog: `{field_name}` is `{value:?}`, but \
+                     this catalog version cannot represent a non-default value for it"
+                ))
+            }
+        }
+    }
+}
+
+/// Migration from v81 to v80: the structural inverse of [`upgrade`], used
+/// when an operator boots an older binary against a catalog that has
+/// already been upgraded to v81.
+///
+/// Returns `Err` if any cluster's `sealed` flag is `true`, since v80 has no
+/// field to preserve that state in.
+pub fn downgrade(
+    snapshot: Vec<v81::StateUpdateKind>,
+) -> Result<Vec<MigrationAction<v81::StateUpdateKind, v80::StateUpdateKind>>, String> {
     snapshot
         .into_iter()
-        .filter_map(|old| {
-            let new: v81::StateUpdateKind = match old.clone() {
-                v80::StateUpdateKind::Cluster(cluster) => {
-                    v81::StateUpdateKind::Cluster(v81::Cluster {
-                        key: v81::ClusterKey {
-                            id: upgrade_cluster_id(cluster.key.id),
+        .filter_map(|new| {
+            let old: v80::StateUpdateKind = match new.clone() {
+                v81::StateUpdateKind::Cluster(cluster) => {
+                    match apply_downgrade_policy(
+                        DowngradePolicy::AbortIfNonDefault,
+                        "sealed",
+                        &cluster.value.config.sealed,
+                    ) {
+                        PolicyOutcome::Proceed => {}
+                        PolicyOutcome::SkipObject => return None,
+                        PolicyOutcome::Abort(err) => return Some(Err(err)),
+                    }
+                    v80::StateUpdateKind::Cluster(v80::Cluster {
+                        key: v80::ClusterKey {
+                            id: downgrade_cluster_id(cluster.key.id),
                         },
-                        value: v81::ClusterValue {
+                        value: v80::ClusterValue {
                             name: cluster.value.name,
-                            owner_id: upgrade_role_id(cluster.value.owner_id),
+                            owner_id: downgrade_role_id(cluster.value.owner_id),
                             privileges: cluster
                                 .value
                                 .privileges
                                 .into_iter()
-                                .map(upgrade_mz_acl_item)
+                                .map(downgrade_mz_acl_item)
                                 .collect(),
-                            config: v81::ClusterConfig {
+                            config: v80::ClusterConfig {
                                 workload_class: cluster.value.config.workload_class,
-                                variant: upgrade_cluster_variant(cluster.value.config.variant),
-                                // New field: default to false (unsealed)
-                                sealed: false,
+                                variant: downgrade_cluster_variant(cluster.value.config.variant),
                             },
                         },
                     })
                 }
-                // All other types are JSON-compatible between v80 and v81
+                // All other types are JSON-compatible between v80 and v81.
                 _ => return None,
             };
-            Some(MigrationAction::Update(old, new))
+            Some(Ok(MigrationAction::Update(old, new).invert()))
         })
         .collect()
 }
 
-fn upgrade_cluster_id(id: v80::ClusterId) -> v81::ClusterId {
+fn downgrade_cluster_id(id: v81::ClusterId) -> v80::ClusterId {
     match id {
-        v80::ClusterId::System(id) => v81::ClusterId::System(id),
-        v80::ClusterId::User(id) => v81::ClusterId::User(id),
+        v81::ClusterId::System(id) => v80::ClusterId::System(id),
+        v81::ClusterId::User(id) => v80::ClusterId::User(id),
     }
 }
 
-fn upgrade_role_id(id: v80::RoleId) -> v81::RoleId {
+fn downgrade_role_id(id: v81::RoleId) -> v80::RoleId {
     match id {
-        v80::RoleId::System(id) => v81::RoleId::System(id),
-        v80::RoleId::User(id) => v81::RoleId::User(id),
-        v80::RoleId::Public => v81::RoleId::Public,
-        v80::RoleId::Predefined(id) => v81::RoleId::Predefined(id),
+        v81::RoleId::System(id) => v80::RoleId::System(id),
+        v81::RoleId::User(id) => v80::RoleId::User(id),
+        v81::RoleId::Public => v80::RoleId::Public,
+        v81::RoleId::Predefined(id) => v80::RoleId::Predefined(id),
     }
 }
 
-fn upgrade_mz_acl_item(item: v80::MzAclItem) -> v81::MzAclItem {
-    v81::MzAclItem {
-        grantee: upgrade_role_id(item.grantee),
-        grantor: upgrade_role_id(item.grantor),
-        acl_mode: v81::AclMode {
+fn downgrade_mz_acl_item(item: v81::MzAclItem) -> v80::MzAclItem {
+    v80::MzAclItem {
+        grantee: downgrade_role_id(item.grantee),
+        grantor: downgrade_role_id(item.grantor),
+        acl_mode: v80::AclMode {
             bitflags: item.acl_mode.bitflags,
         },
     }
 }
 
-fn upgrade_cluster_variant(variant: v80::ClusterVariant) -> v81::ClusterVariant {
+fn downgrade_cluster_variant(variant: v81::ClusterVariant) -> v80::ClusterVariant {
     match variant {
-        v80::ClusterVariant::Unmanaged => v81::ClusterVariant::Unmanaged,
-        v80::ClusterVariant::Managed(m) => v81::ClusterVariant::Managed(v81::ManagedCluster {
+        v81::ClusterVariant::Unmanaged => v80::ClusterVariant::Unmanaged,
+        v81::ClusterVariant::Managed(m) => v80::ClusterVariant::Managed(v80::ManagedCluster {
             size: m.size,
             replication_factor: m.replication_factor,
             availability_zones: m.availability_zones,
-            logging: v81::ReplicaLogging {
+            logging: v80::ReplicaLogging {
                 log_logging: m.logging.log_logging,
-                interval: m.logging.interval.map(|d| v81::Duration {
+                interval: m.logging.interval.map(|d| v80::Duration {
                     secs: d.secs,
                     nanos: d.nanos,
                 }),
@@ -96,16 +311,16 @@ fn upgrade_cluster_variant(variant: v80::ClusterVariant) -> v81::ClusterVariant
             optimizer_feature_overrides: m
                 .optimizer_feature_overrides
                 .into_iter()
-                .map(|o| v81::OptimizerFeatureOverride {
+                .map(|o| v80::OptimizerFeatureOverride {
                     name: o.name,
                     value: o.value,
                 })
                 .collect(),
             schedule: match m.schedule {
-                v80::ClusterSchedule::Manual => v81::ClusterSchedule::Manual,
-                v80::ClusterSchedule::Refresh(r) => {
-                    v81::ClusterSchedule::Refresh(v81::ClusterScheduleRefreshOptions {
-                        rehydration_time_estimate: v81::Duration {
+                v81::ClusterSchedule::Manual => v80::ClusterSchedule::Manual,
+                v81::ClusterSchedule::Refresh(r) => {
+                    v80::ClusterSchedule::Refresh(v80::ClusterScheduleRefreshOptions {
+                        rehydration_time_estimate: v80::Duration {
                             secs: r.rehydration_time_estimate.secs,
                             nanos: r.rehydration_time_estimate.nanos,
                         },
@@ -115,3 +330,68 @@ fn upgrade_cluster_variant(variant: v80::ClusterVariant) -> v81::ClusterVariant
         }),
     }
 }
+
+fn upgrade_cluster_variant(
+    variant: v80::ClusterVariant,
+    caches: &UpgradeCaches,
+) -> v81::ClusterVariant {
+    match variant {
+        v80::ClusterVariant::Unmanaged => v81::ClusterVariant::Unmanaged,
+        v80::ClusterVariant::Managed(m) => {
+            // Keyed on the sub-value itself (not the whole `ManagedCluster`
+            // or `ClusterVariant`), so clusters that share a logging config
+            // or override list cache-hit even if they differ in size,
+            // replication factor, or availability zones.
+            let logging = m.logging.clone();
+            let upgraded_logging = caches
+                .logging
+                .get_or_convert(logging, || upgrade_replica_logging(m.logging));
+            let overrides = m.optimizer_feature_overrides.clone();
+            let upgraded_overrides = caches
+                .optimizer_feature_overrides
+                .get_or_convert(overrides, || {
+                    upgrade_optimizer_feature_overrides(m.optimizer_feature_overrides)
+                });
+            v81::ClusterVariant::Managed(v81::ManagedCluster {
+                size: m.size,
+                replication_factor: m.replication_factor,
+                availability_zones: m.availability_zones,
+                logging: upgraded_logging,
+                optimizer_feature_overrides: upgraded_overrides,
+                schedule: match m.schedule {
+                    v80::ClusterSchedule::Manual => v81::ClusterSchedule::Manual,
+                    v80::ClusterSchedule::Refresh(r) => {
+                        v81::ClusterSchedule::Refresh(v81::ClusterScheduleRefreshOptions {
+                            rehydration_time_estimate: v81::Duration {
+                                secs: r.rehydration_time_estimate.secs,
+                                nanos: r.rehydration_time_estimate.nanos,
+                            },
+                        })
+                    }
+                },
+            })
+        }
+    }
+}
+
+fn upgrade_replica_logging(logging: v80::ReplicaLogging) -> v81::ReplicaLogging {
+    v81::ReplicaLogging {
+        log_logging: logging.log_logging,
+        interval: logging.interval.map(|d| v81::Duration {
+            secs: d.secs,
+            nanos: d.nanos,
+        }),
+    }
+}
+
+fn upgrade_optimizer_feature_overrides(
+    overrides: Vec<v80::OptimizerFeatureOverride>,
+) -> Vec<v81::OptimizerFeatureOverride> {
+    overrides
+        .into_iter()
+        .map(|o| v81::OptimizerFeatureOverride {
+            name: o.name,
+            value: o.value,
+        })
+        .collect()
+}
@@ -0,0 +1,262 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use proptest::prelude::*;
+
+use super::*;
+
+/// Generates arbitrary v80 `Cluster` state updates, covering `Unmanaged`
+/// and `Managed` variants with zero to three privileges. `workload_class`
+/// is always `None`, and managed clusters use a `Manual` schedule, no
+/// logging interval, and no availability zones or optimizer overrides.
+fn arb_cluster() -> impl Strategy<Value = v80::StateUpdateKind> {
+    let arb_variant = prop_oneof![
+        Just(v80::ClusterVariant::Unmanaged),
+        (any::<String>(), 1u32..8, any::<bool>()).prop_map(|(size, replication_factor, log_logging)| {
+            v80::ClusterVariant::Managed(v80::ManagedCluster {
+                size,
+                replication_factor,
+                availability_zones: Vec::new(),
+                logging: v80::ReplicaLogging {
+                    log_logging,
+                    interval: None,
+                },
+                optimizer_feature_overrides: Vec::new(),
+                schedule: v80::ClusterSchedule::Manual,
+            })
+        }),
+    ];
+
+    let arb_privileges = prop::collection::vec(arb_mz_acl_item(), 0..4);
+
+    (
+        any::<u64>(),
+        any::<String>(),
+        any::<u64>(),
+        arb_variant,
+        arb_privileges,
+    )
+        .prop_map(|(id, name, owner_id, variant, privileges)| {
+            v80::StateUpdateKind::Cluster(v80::Cluster {
+                key: v80::ClusterKey {
+                    id: v80::ClusterId::User(id),
+                },
+                value: v80::ClusterValue {
+                    name,
+                    owner_id: v80::RoleId::User(owner_id),
+                    privileges,
+                    config: v80::ClusterConfig {
+                        workload_class: None,
+                        variant,
+                    },
+                },
+            })
+        })
+}
+
+fn arb_mz_acl_item() -> impl Strategy<Value = v80::MzAclItem> {
+    (any::<u64>(), any::<u64>(), any::<u64>()).prop_map(|(grantee, grantor, bitflags)| {
+        v80::MzAclItem {
+            grantee: v80::RoleId::User(grantee),
+            grantor: v80::RoleId::User(grantor),
+            acl_mode: v80::AclMode { bitflags },
+        }
+    })
+}
+
+/// A v80 `Role` state update: JSON-compatible between v80 and v81, so
+/// `upgrade` must pass it through untouched (no `MigrationAction`).
+fn arb_role() -> impl Strategy<Value = v80::StateUpdateKind> {
+    (any::<u64>(), any::<String>()).prop_map(|(id, name)| {
+        v80::StateUpdateKind::Role(v80::Role {
+            key: v80::RoleKey {
+                id: v80::RoleId::User(id),
+            },
+            value: v80::RoleValue { name },
+        })
+    })
+}
+
+proptest! {
+    /// A `Cluster`'s `name`, `owner_id`, `privileges`, `workload_class`, and
+    /// `variant` must survive the migration unchanged (accounting for the
+    /// v80 -> v81 id/acl conversions), and the new `sealed` field must
+    /// always come out `false`.
+    #[test]
+    fn upgrade_preserves_cluster_fields(old in arb_cluster()) {
+        let v80::StateUpdateKind::Cluster(old_cluster) = old.clone() else {
+            unreachable!("arb_cluster only generates Cluster state updates");
+        };
+
+        let actions = upgrade(vec![old]);
+        prop_assert_eq!(actions.len(), 1);
+        let MigrationAction::Update(_, v81::StateUpdateKind::Cluster(new_cluster)) = &actions[0] else {
+            panic!("expected a single Cluster update");
+        };
+
+        prop_assert_eq!(&new_cluster.value.name, &old_cluster.value.name);
+        prop_assert_eq!(
+            new_cluster.value.owner_id.clone(),
+            upgrade_role_id(old_cluster.value.owner_id.clone())
+        );
+        let expected_privileges: Vec<_> = old_cluster
+            .value
+            .privileges
+            .clone()
+            .into_iter()
+            .map(upgrade_mz_acl_item)
+            .collect();
+        prop_assert_eq!(&new_cluster.value.privileges, &expected_privileges);
+        prop_assert_eq!(
+            &new_cluster.value.config.workload_class,
+            &old_cluster.value.config.workload_class
+        );
+        prop_assert_eq!(
+            new_cluster.value.config.variant.clone(),
+            upgrade_cluster_variant(old_cluster.value.config.variant, &UpgradeCaches::new())
+        );
+        prop_assert_eq!(new_cluster.value.config.sealed, false);
+    }
+
+    /// Every non-`Cluster` variant is JSON-compatible between v80 and v81
+    /// and must be emitted unchanged: `upgrade` produces no
+    /// `MigrationAction` for it. Exercised here with `Role` state updates.
+    #[test]
+    fn upgrade_leaves_non_cluster_variants_untouched(old in arb_role()) {
+        let actions = upgrade(vec![old]);
+        prop_assert_eq!(actions.len(), 0);
+    }
+
+    /// Once a round trip through `upgrade` then `downgrade` succeeds (i.e.
+    /// the cluster wasn't sealed), the result must equal the original
+    /// input.
+    #[test]
+    fn upgrade_then_downgrade_roundtrips(old in arb_cluster()) {
+        assert_migration_roundtrip(old);
+    }
+}
+
+/// Runs `old` through [`upgrade`] and then [`downgrade`], asserting that the
+/// non-lossy fields come back unchanged. Written for the v80/v81 pair only;
+/// later version steps can reuse the same shape once they have their own
+/// paired `upgrade`/`downgrade`.
+fn assert_migration_roundtrip(old: v80::StateUpdateKind) {
+    let upgraded = upgrade(vec![old.clone()]);
+    let new = match upgraded.as_slice() {
+        [MigrationAction::Update(_, new)] => new.clone(),
+        [] => return,
+        _ => panic!("expected at most one migration action"),
+    };
+
+    let downgraded = downgrade(vec![new]).expect("unsealed cluster downgrades cleanly");
+    let roundtripped = match downgraded.as_slice() {
+        [MigrationAction::Update(_, roundtripped)] => roundtripped.clone(),
+        _ => panic!("expected exactly one migration action"),
+    };
+
+    assert_eq!(roundtripped, old);
+}
+
+/// The scenario the backlog calls out by name: a catalog that has
+/// `sealed = true` clusters must fail loudly on downgrade rather than
+/// silently discarding the flag.
+#[test]
+fn downgrade_aborts_on_sealed_cluster() {
+    let sealed_cluster = v81::StateUpdateKind::Cluster(v81::Cluster {
+        key: v81::ClusterKey {
+            id: v81::ClusterId::User(1),
+        },
+        value: v81::ClusterValue {
+            name: "sealed_cluster".to_string(),
+            owner_id: v81::RoleId::User(1),
+            privileges: Vec::new(),
+            config: v81::ClusterConfig {
+                workload_class: None,
+                variant: v81::ClusterVariant::Unmanaged,
+                sealed: true,
+            },
+        },
+    });
+
+    let err = downgrade(vec![sealed_cluster]).unwrap_err();
+    assert!(err.contains("sealed"));
+}
+
+/// `downgrade` succeeds and drops `sealed` when it's already `false`.
+#[test]
+fn downgrade_succeeds_on_unsealed_cluster() {
+    let unsealed_cluster = v81::StateUpdateKind::Cluster(v81::Cluster {
+        key: v81::ClusterKey {
+            id: v81::ClusterId::User(1),
+        },
+        value: v81::ClusterValue {
+            name: "unsealed_cluster".to_string(),
+            owner_id: v81::RoleId::User(1),
+            privileges: Vec::new(),
+            config: v81::ClusterConfig {
+                workload_class: None,
+                variant: v81::ClusterVariant::Unmanaged,
+                sealed: false,
+            },
+        },
+    });
+
+    let actions = downgrade(vec![unsealed_cluster]).expect("unsealed cluster downgrades cleanly");
+    assert_eq!(actions.len(), 1);
+}
+
+#[test]
+fn downgrade_policy_drop_silently_always_proceeds() {
+    assert_eq!(
+        apply_downgrade_policy(DowngradePolicy::DropSilently, "field", &true),
+        PolicyOutcome::Proceed
+    );
+}
+
+#[test]
+fn downgrade_policy_require_default_skips_the_object_on_mismatch() {
+    assert_eq!(
+        apply_downgrade_policy(DowngradePolicy::RequireDefault, "field", &false),
+        PolicyOutcome::Proceed
+    );
+    assert_eq!(
+        apply_downgrade_policy(DowngradePolicy::RequireDefault, "field", &true),
+        PolicyOutcome::SkipObject
+    );
+}
+
+#[test]
+fn downgrade_policy_abort_if_non_default_fails_the_whole_downgrade_on_mismatch() {
+    assert_eq!(
+        apply_downgrade_policy(DowngradePolicy::AbortIfNonDefault, "field", &false),
+        PolicyOutcome::Proceed
+    );
+    assert!(matches!(
+        apply_downgrade_policy(DowngradePolicy::AbortIfNonDefault, "field", &true),
+        PolicyOutcome::Abort(_)
+    ));
+}
+
+/// Loads a real serialized v80 snapshot and drives it through the migration,
+/// catching regressions where the transform assumes a field that a fixture
+/// captured from an older version didn't populate.
+#[test]
+fn fixture_v80_cluster_snapshot_migrates() {
+    let raw = include_str!("../testdata/v80_cluster_snapshot.json");
+    let snapshot: Vec<v80::StateUpdateKind> =
+        serde_json::from_str(raw).expect("fixture is valid v80 StateUpdateKind JSON");
+
+    let actions = upgrade(snapshot);
+    for action in &actions {
+        let MigrationAction::Update(_, v81::StateUpdateKind::Cluster(cluster)) = action else {
+            panic!("only the fixture's Cluster state updates produce migration actions");
+        };
+        assert!(!cluster.value.config.sealed);
+    }
+}